@@ -0,0 +1,61 @@
+use crate::cell::Cell;
+use crate::solver::Board;
+
+// Maps a board character to its value: '.'/'0' for empty, '1'-'9' then letters
+// (case-insensitive, 'A' = 10, 'B' = 11, ...) for values past 9, so a 16x16
+// board uses '1'-'9' and 'A'-'G'. Any other character is ignored.
+fn char_to_value(c: char) -> Option<u8> {
+    match c {
+        '.' | '0' => Some(0),
+        '1'..='9' => Some(c.to_digit(10).unwrap() as u8),
+        'a'..='z' => Some(10 + (c as u8 - b'a')),
+        'A'..='Z' => Some(10 + (c as u8 - b'A')),
+        _ => None,
+    }
+}
+
+fn value_to_char(v: u8) -> char {
+    if v < 10 {
+        ::std::char::from_digit(v.into(), 10).unwrap()
+    } else {
+        (b'A' + (v - 10)) as char
+    }
+}
+
+pub fn load_board(s: &str, n: usize) -> Board {
+    let mut board = Vec::with_capacity(n * n);
+
+    for c in s.chars() {
+        let value = match char_to_value(c) {
+            Some(v) => v,
+            None => continue,
+        };
+
+        if value == 0 {
+            // Empty cell
+            board.push(Cell::new_all_set(n));
+        } else {
+            // Given cell
+            let mut cell = Cell::new();
+            cell.set(value);
+            board.push(cell);
+        }
+    }
+
+    if board.len() != n * n {
+        panic!("Too few cells in board {}", s)
+    }
+
+    board
+}
+
+pub fn serialize_board(board: &Board) -> String {
+    board.iter().map(|cell| {
+        if cell.size() == 1 {
+            value_to_char(cell.get_first())
+        } else {
+            '.'
+        }
+    }).collect()
+}
+
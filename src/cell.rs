@@ -0,0 +1,87 @@
+use std::fmt;
+
+// An iterator over the set bits in a u32, one-indexed
+// Example: a BitIterator over the number 0b101101 will yield 1, 3, 4, 6
+pub struct BitIterator(u32, u8);
+impl Iterator for BitIterator {
+    type Item = u8;
+    fn next(&mut self) -> Option<u8> {
+        while self.0 != 0 {
+            let bit = self.0 & 1;
+            self.0 >>= 1;
+            self.1 += 1;
+
+            if bit > 0 {
+                return Some(self.1)
+            }
+        }
+        None
+    }
+}
+
+// The full candidate mask for a grid of side length `n` (all values 1..=n set).
+// A u32 is wide enough to cover every variant this crate supports, up to 16x16.
+pub fn all_nums(n: usize) -> u32 {
+    if n >= 32 {
+        u32::MAX
+    } else {
+        (1 << n) - 1
+    }
+}
+
+// A cell's set of remaining candidates, represented as a bitset so that the
+// engine works the same whether a cell permits 4 values or 16.
+#[derive(Clone, Copy)]
+pub struct Cell(u32);
+
+impl Cell {
+    pub fn new() -> Self {
+        Cell(0)
+    }
+
+    // A cell with every value 1..=n still a candidate.
+    pub fn new_all_set(n: usize) -> Self {
+        Cell(all_nums(n))
+    }
+
+    pub fn set(&mut self, num: u8) {
+        self.0 |= 1 << (num - 1);
+    }
+
+    pub fn unset(&mut self, num: u8) {
+        self.0 &= !(1 << (num - 1));
+    }
+
+    pub fn clear(&mut self) {
+        self.0 = 0;
+    }
+
+    pub fn get(&self, num: u8) -> bool {
+        (self.0 & (1 << (num - 1))) > 0
+    }
+
+    pub fn size(&self) -> u32 {
+        self.0.count_ones()
+    }
+
+    pub fn get_first(&self) -> u8 {
+        self.0.trailing_zeros() as u8 + 1
+    }
+
+    pub fn remove_all(&mut self, other: &Cell) {
+        self.0 &= !other.0
+    }
+
+    pub fn iter(&self) -> BitIterator {
+        BitIterator(self.0, 0)
+    }
+}
+impl fmt::Display for Cell {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for n in self.iter() {
+            write!(f, "{}", n)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,73 @@
+use std::fmt;
+
+// Counts of how much work each technique did while solving a board: how many
+// cells naked singles, hidden singles, and locked candidates each fixed or
+// narrowed, and how many guesses the MRV search made (and had to undo).
+#[derive(Default, Clone, Copy)]
+pub struct Stats {
+    pub naked_singles: usize,
+    pub hidden_singles: usize,
+    pub locked_candidates: usize,
+    pub guesses: usize,
+    pub backtracks: usize,
+}
+
+// Below this many backtracks, a search-requiring board is rated Hard rather
+// than Expert. `backtracks` can never exceed `guesses` (every backtrack undoes
+// a prior guess), so the tiers are split on an absolute amount of backtracking
+// instead of comparing the two counts against each other.
+const EXPERT_BACKTRACK_THRESHOLD: usize = 20;
+
+impl Stats {
+    // A human-facing difficulty rating derived from which techniques were
+    // needed: pure naked singles is Easy, needing hidden singles or locked
+    // candidates is Medium, needing the search at all is Hard, and a search
+    // that backtracked a lot to find its solution is Expert.
+    pub fn rating(&self) -> Rating {
+        if self.guesses > 0 {
+            if self.backtracks >= EXPERT_BACKTRACK_THRESHOLD {
+                Rating::Expert
+            } else {
+                Rating::Hard
+            }
+        } else if self.hidden_singles > 0 || self.locked_candidates > 0 {
+            Rating::Medium
+        } else {
+            Rating::Easy
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rating {
+    Easy,
+    Medium,
+    Hard,
+    Expert,
+}
+
+impl fmt::Display for Rating {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Rating::Easy => "Easy",
+            Rating::Medium => "Medium",
+            Rating::Hard => "Hard",
+            Rating::Expert => "Expert",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rates_heavy_backtracking_as_expert_rather_than_hard() {
+        let light = Stats { guesses: 10, backtracks: 3, ..Stats::default() };
+        assert_eq!(light.rating(), Rating::Hard);
+
+        let heavy = Stats { guesses: 50, backtracks: EXPERT_BACKTRACK_THRESHOLD, ..Stats::default() };
+        assert_eq!(heavy.rating(), Rating::Expert);
+    }
+}
@@ -0,0 +1,73 @@
+use rand::prelude::*;
+
+use crate::cell::Cell;
+use crate::difficulty::Stats;
+use crate::solver::{count_solutions, find_smallest_cell, propagate, Board, Context};
+
+// Fills a blank board into a complete, valid board by running the same
+// propagate-then-search as solve_with_budget, but trying each cell's
+// candidates in a shuffled order instead of ascending, so repeated calls
+// yield different boards.
+fn fill_random(board: Board, ctx: &Context, rng: &mut impl Rng) -> Option<Board> {
+    let mut stats = Stats::default();
+    let (board, solved) = propagate(board, ctx, &mut stats)?;
+
+    if solved {
+        return Some(board);
+    }
+
+    let smallest_cell = find_smallest_cell(&board);
+    let mut candidates: Vec<u8> = board[smallest_cell].iter().collect();
+    candidates.shuffle(rng);
+
+    for val in candidates {
+        let mut board2 = board.clone();
+        board2[smallest_cell].clear();
+        board2[smallest_cell].set(val);
+
+        if let Some(solved) = fill_random(board2, ctx, rng) {
+            return Some(solved);
+        }
+    }
+
+    None
+}
+
+// Generates a puzzle with a unique solution: fills a blank board at random,
+// then repeatedly blanks a random clue, keeping the removal only if the board
+// still has exactly one solution. Stops once no further cell can be removed,
+// or once `target_clues` is reached (if given).
+pub fn generate(ctx: &Context, target_clues: Option<usize>) -> Board {
+    let mut rng = rand::thread_rng();
+
+    let n = ctx.spec.side;
+    let blank = vec![Cell::new_all_set(n); n * n];
+    let mut puzzle = fill_random(blank, ctx, &mut rng)
+        .expect("a blank board always has a solution");
+
+    let mut cells: Vec<usize> = (0..puzzle.len()).collect();
+    cells.shuffle(&mut rng);
+
+    let mut clues_remaining = puzzle.len();
+
+    for i in cells {
+        if let Some(target) = target_clues {
+            if clues_remaining <= target {
+                break;
+            }
+        }
+
+        let removed_value = puzzle[i].get_first();
+        puzzle[i] = Cell::new_all_set(n);
+
+        if count_solutions(puzzle.clone(), 2, ctx) == 1 {
+            clues_remaining -= 1;
+        } else {
+            // Removing this clue made the puzzle ambiguous (or unsolvable); put it back.
+            puzzle[i].clear();
+            puzzle[i].set(removed_value);
+        }
+    }
+
+    puzzle
+}
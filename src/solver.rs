@@ -0,0 +1,377 @@
+use crate::cell::Cell;
+use crate::difficulty::Stats;
+use crate::variant::{derive_peers, GridSpec, Variant};
+
+pub type Board = Vec<Cell>;
+
+// Everything the solver needs for a given grid shape and variant, computed once
+// up front: the variant's units and each cell's peers derived from them.
+pub struct Context {
+    pub spec: GridSpec,
+    pub units: Vec<Vec<usize>>,
+    pub peers: Vec<Vec<usize>>,
+}
+
+impl Context {
+    pub fn new(spec: GridSpec, variant: &dyn Variant) -> Self {
+        let units = variant.units(&spec);
+        let peers = derive_peers(&spec, &units);
+        Context { spec, units, peers }
+    }
+}
+
+// Hidden singles: if a value can only go in one cell of a unit, it must go there,
+// even if that cell still has other candidates too.
+// Returns None on contradiction (a value has nowhere left to go in some unit),
+// otherwise whether any cell was narrowed down to a single candidate.
+fn apply_hidden_singles(board: &mut Board, ctx: &Context, stats: &mut Stats) -> Option<bool> {
+    let mut made_progress = false;
+
+    for unit in ctx.units.iter() {
+        for v in 1..=ctx.spec.side as u8 {
+            let mut only_cell = None;
+            let mut count = 0;
+
+            for &i in unit.iter() {
+                if board[i].get(v) {
+                    only_cell = Some(i);
+                    count += 1;
+                }
+            }
+
+            match count {
+                0 => return None, // no cell in this unit can hold v anymore.
+                1 => {
+                    let i = only_cell.unwrap();
+                    if board[i].size() > 1 {
+                        board[i].clear();
+                        board[i].set(v);
+                        made_progress = true;
+                        stats.hidden_singles += 1;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    Some(made_progress)
+}
+
+// Locked candidates (pointing): if within a box, every cell that still permits a
+// value lies in a single row or column, that value can be removed from the rest
+// of that row/column outside the box. This relies on the box geometry in
+// GridSpec directly, since a Variant's extra units (e.g. diagonals) don't carry
+// that structure.
+fn apply_locked_candidates(board: &mut Board, ctx: &Context, stats: &mut Stats) -> bool {
+    let spec = &ctx.spec;
+    let n = spec.side;
+    let mut made_progress = false;
+
+    for block_row in (0..n).step_by(spec.box_h) {
+        for block_col in (0..n).step_by(spec.box_w) {
+            for v in 1..=n as u8 {
+                let mut rows = Cell::new();
+                let mut cols = Cell::new();
+
+                for r in block_row..block_row + spec.box_h {
+                    for c in block_col..block_col + spec.box_w {
+                        if board[r * n + c].get(v) {
+                            rows.set((r - block_row + 1) as u8);
+                            cols.set((c - block_col + 1) as u8);
+                        }
+                    }
+                }
+
+                if rows.size() == 1 {
+                    let row = block_row + (rows.get_first() - 1) as usize;
+                    for col in 0..n {
+                        if col >= block_col && col < block_col + spec.box_w { continue; }
+                        let i = row * n + col;
+                        if board[i].get(v) {
+                            board[i].unset(v);
+                            made_progress = true;
+                            stats.locked_candidates += 1;
+                        }
+                    }
+                }
+
+                if cols.size() == 1 {
+                    let col = block_col + (cols.get_first() - 1) as usize;
+                    for row in 0..n {
+                        if row >= block_row && row < block_row + spec.box_h { continue; }
+                        let i = row * n + col;
+                        if board[i].get(v) {
+                            board[i].unset(v);
+                            made_progress = true;
+                            stats.locked_candidates += 1;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    made_progress
+}
+
+// Runs constraint propagation (naked singles, hidden singles, locked candidates)
+// to a fixed point. Returns None on contradiction, otherwise the narrowed board
+// and whether it's already completely solved.
+pub(crate) fn propagate(mut board: Board, ctx: &Context, stats: &mut Stats) -> Option<(Board, bool)> {
+    let cell_count = ctx.spec.side * ctx.spec.side;
+
+    // Keeps track of whether any rule changed the board this iteration (a cell became
+    // certain, or locked candidates removed a candidate)
+    // If nothing changed, we won't be able to make any more progress using contraint propagation
+    let mut made_progress = true;
+    // Keeps track of whether the board has been solved or not
+    // This is used to determine whether to continue with a search afterwards or not
+    let mut solved = false;
+    while made_progress {
+        made_progress = false;
+        solved = true;
+        for i in 0..cell_count {
+            // If the cell already has a known value, there isn't anything to do
+            if board[i].size() == 1 { continue; }
+
+            // This set will contain all of the values that this cell cannot be
+            // (because it has a peer that is already that value)
+            let mut peer_values = Cell::new();
+
+            for &peer_i in ctx.peers[i].iter() {
+                let peer = board[peer_i];
+                // Only if we are certain about the value of this peer:
+                if peer.size() == 1 {
+                    // Since the peer only has one possible value, get_first returns the value of the cell.
+                    peer_values.set(peer.get_first());
+                }
+            }
+
+            let cell = board.get_mut(i).unwrap();
+            cell.remove_all(&peer_values);
+
+            match cell.size() {
+                0 => return None, // conflict found, board can't be solved.
+                1 => {
+                    made_progress = true; // cell wasn't certain before, now it is.
+                    stats.naked_singles += 1;
+                }
+                _ => solved = false, // there are still multiple possibilites, so the board won't be solved this iteration.
+            }
+        }
+
+        // Only escalate to hidden singles and locked candidates once naked
+        // singles alone have stalled: a board solvable by naked singles alone
+        // should never count toward a higher difficulty tier.
+        if !made_progress && !solved {
+            match apply_hidden_singles(&mut board, ctx, stats) {
+                None => return None,
+                Some(progress) => if progress { made_progress = true; },
+            }
+
+            if apply_locked_candidates(&mut board, ctx, stats) {
+                made_progress = true;
+            }
+        }
+    }
+
+    Some((board, solved))
+}
+
+// The cell with the fewest remaining candidates (but more than one), i.e. the
+// one the search below should branch on first (minimum remaining values).
+pub(crate) fn find_smallest_cell(board: &Board) -> usize {
+    board
+        .iter()
+        .enumerate()
+        .filter(|(_, cell)| cell.size() > 1)
+        .min_by_key(|(_, cell)| cell.size())
+        .map(|(i, _)| i)
+        .expect("find_smallest_cell called on a fully-solved board")
+}
+
+// The result of a bounded solve: either a solution, proof that none exists, or
+// a search that was aborted after exhausting its node budget.
+pub enum SolveOutcome {
+    Solved(Board),
+    Unsolvable,
+    TimedOut,
+}
+
+// `budget` is the number of search nodes (guesses, including the top-level
+// call) still allowed before the search gives up and reports a timeout, so a
+// pathological board can't hang the caller forever.
+fn solve_tracked(board: Board, ctx: &Context, stats: &mut Stats, budget: &mut usize) -> SolveOutcome {
+    if *budget == 0 {
+        return SolveOutcome::TimedOut;
+    }
+    *budget -= 1;
+
+    let (board, solved) = match propagate(board, ctx, stats) {
+        None => return SolveOutcome::Unsolvable,
+        Some(r) => r,
+    };
+
+    if solved {
+        SolveOutcome::Solved(board)
+    } else {
+        // We can't make any more progress with contstring propagation, so it is time to start a search algorithm
+        let smallest_cell = find_smallest_cell(&board);
+
+        // Try out every possible value of that cell
+        for val in board[smallest_cell].iter() {
+            stats.guesses += 1;
+
+            let mut board2 = board.clone();
+            // Set the cell to the value we're trying
+            board2[smallest_cell].clear();
+            board2[smallest_cell].set(val);
+
+            // Try to solve the new board with the test value
+            match solve_tracked(board2, ctx, stats, budget) {
+                // If that value works, we're done. Otherwise we'll continue with the next value
+                SolveOutcome::Solved(solved) => return SolveOutcome::Solved(solved),
+                SolveOutcome::TimedOut => return SolveOutcome::TimedOut,
+                SolveOutcome::Unsolvable => stats.backtracks += 1,
+            }
+        }
+
+        SolveOutcome::Unsolvable
+    }
+}
+
+// Solves a board, giving up and reporting a timeout once `budget` search
+// nodes have been explored rather than searching forever. Pass `usize::MAX`
+// for an effectively unbounded search.
+pub fn solve_with_budget(board: Board, ctx: &Context, budget: usize) -> (SolveOutcome, Stats) {
+    let mut stats = Stats::default();
+    let mut remaining = budget;
+    let outcome = solve_tracked(board, ctx, &mut stats, &mut remaining);
+    (outcome, stats)
+}
+
+// Counts how many distinct solutions a board has, stopping early once `limit`
+// is reached. Used to check a puzzle is uniquely solvable (pass `limit = 2`)
+// without paying for an exhaustive count.
+pub fn count_solutions(board: Board, limit: usize, ctx: &Context) -> usize {
+    let mut stats = Stats::default();
+    let (board, solved) = match propagate(board, ctx, &mut stats) {
+        None => return 0,
+        Some(r) => r,
+    };
+
+    if solved {
+        return 1;
+    }
+
+    let smallest_cell = find_smallest_cell(&board);
+    let mut count = 0;
+
+    for val in board[smallest_cell].iter() {
+        let mut board2 = board.clone();
+        board2[smallest_cell].clear();
+        board2[smallest_cell].set(val);
+
+        count += count_solutions(board2, limit - count, ctx);
+        if count >= limit {
+            break;
+        }
+    }
+
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{load_board, serialize_board};
+    use crate::variant::StandardVariant;
+
+    fn standard_ctx() -> Context {
+        Context::new(GridSpec::standard_9x9(), &StandardVariant)
+    }
+
+    // A puzzle that needs hidden singles and locked candidates beyond naked
+    // singles alone, so this exercises propagate()'s full fixed-point loop,
+    // not just the search fallback.
+    const PUZZLE: &str =
+        "1.4.6....29...8.1......173....2.39.8.7.1.9.......86...76.....4...8...3.1..1..2657";
+    const SOLUTION: &str =
+        "134967285297538416685421739416253978872149563953786124769315842528674391341892657";
+
+    #[test]
+    fn solves_a_puzzle_using_propagation() {
+        let ctx = standard_ctx();
+        let board = load_board(PUZZLE, ctx.spec.side);
+
+        let (outcome, stats) = solve_with_budget(board, &ctx, usize::MAX);
+        match outcome {
+            SolveOutcome::Solved(solved) => assert_eq!(serialize_board(&solved), SOLUTION),
+            _ => panic!("expected the puzzle to solve"),
+        }
+        assert!(stats.hidden_singles > 0 || stats.locked_candidates > 0);
+    }
+
+    #[test]
+    fn counts_a_unique_puzzle_as_one_solution() {
+        let ctx = standard_ctx();
+        let board = load_board(PUZZLE, ctx.spec.side);
+
+        assert_eq!(count_solutions(board, 2, &ctx), 1);
+    }
+
+    #[test]
+    fn counts_an_ambiguous_puzzle_as_at_least_two_solutions() {
+        // PUZZLE with one more clue blanked out, which admits a second solution.
+        const AMBIGUOUS: &str =
+            "..4.6....29...8.1......173....2.39.8.7.1.9.......86...76.....4...8...3.1..1..2657";
+
+        let ctx = standard_ctx();
+        let board = load_board(AMBIGUOUS, ctx.spec.side);
+
+        assert_eq!(count_solutions(board, 2, &ctx), 2);
+    }
+
+    #[test]
+    fn a_board_solvable_by_naked_singles_alone_rates_easy() {
+        // SOLUTION with a single cell blanked: every remaining candidate for
+        // that cell but one is eliminated by its peers, so naked singles alone
+        // finish the board without ever needing hidden singles or locked
+        // candidates.
+        const ALMOST_SOLVED: &str =
+            ".34967285297538416685421739416253978872149563953786124769315842528674391341892657";
+
+        let ctx = standard_ctx();
+        let board = load_board(ALMOST_SOLVED, ctx.spec.side);
+
+        let (outcome, stats) = solve_with_budget(board, &ctx, usize::MAX);
+        match outcome {
+            SolveOutcome::Solved(solved) => assert_eq!(serialize_board(&solved), SOLUTION),
+            _ => panic!("expected the puzzle to solve"),
+        }
+        assert_eq!(stats.hidden_singles, 0);
+        assert_eq!(stats.locked_candidates, 0);
+        assert_eq!(stats.rating(), crate::difficulty::Rating::Easy);
+    }
+
+    // Solving only ever gets exercised against a 9x9 standard grid above;
+    // cover the generic GridSpec/Variant path with a smaller, differently
+    // shaped grid (4x4 with 2x2 boxes) to catch a regression in the
+    // derived-peers/units/bitset machinery that a 9x9-only test would miss.
+    #[test]
+    fn solves_a_4x4_puzzle() {
+        let spec = GridSpec::new(4, 2, 2);
+        let ctx = Context::new(spec, &StandardVariant);
+
+        let puzzle = load_board("3.2......31..2.4", spec.side);
+        let (outcome, _) = solve_with_budget(puzzle, &ctx, usize::MAX);
+
+        match outcome {
+            SolveOutcome::Solved(solved) => {
+                assert_eq!(serialize_board(&solved), "3421214343121234")
+            }
+            _ => panic!("expected the 4x4 puzzle to solve"),
+        }
+    }
+}
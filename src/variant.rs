@@ -0,0 +1,96 @@
+use std::collections::BTreeSet;
+
+// Describes the shape of a grid: its side length, and the width/height of the
+// boxes that make up one of its rows (e.g. 3x3 for standard sudoku, 4x4 for
+// hexadoku, 2x3 for a 6x6 grid).
+#[derive(Clone, Copy)]
+pub struct GridSpec {
+    pub side: usize,
+    pub box_w: usize,
+    pub box_h: usize,
+}
+
+impl GridSpec {
+    pub fn new(side: usize, box_w: usize, box_h: usize) -> Self {
+        GridSpec { side, box_w, box_h }
+    }
+
+    // The classic 9x9 grid of 3x3 boxes.
+    pub fn standard_9x9() -> Self {
+        GridSpec::new(9, 3, 3)
+    }
+}
+
+// A Variant produces the units of a grid: the sets of cell indices that must
+// each contain every value 1..=side exactly once. The solving engine only ever
+// deals with units and peers derived from them, so a new variant can change
+// the rules of the puzzle without touching the engine at all.
+pub trait Variant {
+    fn units(&self, spec: &GridSpec) -> Vec<Vec<usize>>;
+}
+
+// Rows, columns, and boxes: the rules of ordinary sudoku.
+pub struct StandardVariant;
+
+impl Variant for StandardVariant {
+    fn units(&self, spec: &GridSpec) -> Vec<Vec<usize>> {
+        let n = spec.side;
+        let mut units = Vec::with_capacity(3 * n);
+
+        for row in 0..n {
+            units.push((0..n).map(|col| row * n + col).collect());
+        }
+
+        for col in 0..n {
+            units.push((0..n).map(|row| row * n + col).collect());
+        }
+
+        for box_row in (0..n).step_by(spec.box_h) {
+            for box_col in (0..n).step_by(spec.box_w) {
+                let mut unit = Vec::with_capacity(n);
+                for r in box_row..box_row + spec.box_h {
+                    for c in box_col..box_col + spec.box_w {
+                        unit.push(r * n + c);
+                    }
+                }
+                units.push(unit);
+            }
+        }
+
+        units
+    }
+}
+
+// Standard rules, plus the two main diagonals (diagonal/X-sudoku).
+pub struct DiagonalVariant;
+
+impl Variant for DiagonalVariant {
+    fn units(&self, spec: &GridSpec) -> Vec<Vec<usize>> {
+        let mut units = StandardVariant.units(spec);
+        let n = spec.side;
+
+        units.push((0..n).map(|i| i * n + i).collect());
+        units.push((0..n).map(|i| i * n + (n - 1 - i)).collect());
+
+        units
+    }
+}
+
+// Derives each cell's peers (every other cell sharing at least one unit with
+// it) from a variant's units, rather than relying on a compile-time table.
+pub fn derive_peers(spec: &GridSpec, units: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    let cell_count = spec.side * spec.side;
+    let mut peers: Vec<BTreeSet<usize>> = vec![BTreeSet::new(); cell_count];
+
+    for unit in units {
+        for &i in unit {
+            for &j in unit {
+                if i != j {
+                    peers[i].insert(j);
+                }
+            }
+        }
+    }
+
+    peers.into_iter().map(|p| p.into_iter().collect()).collect()
+}